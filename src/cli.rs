@@ -0,0 +1,115 @@
+use clap::{App, Arg, SubCommand};
+
+pub fn get_cli<'a>() -> clap::ArgMatches<'a> {
+    App::new("wscat-rs")
+        .about("A command-line WebSocket client and server")
+        .subcommand(
+            SubCommand::with_name("connect")
+                .about("Connect to a WebSocket server")
+                .arg(
+                    Arg::with_name("URL")
+                        .help("ws:// or wss:// url to connect to")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("USERNAME:PASSWORD")
+                        .help("HTTP Basic auth credentials, e.g. alice:hunter2")
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("no-check")
+                        .long("no-check")
+                        .help("Don't verify the server's TLS certificate (wss:// only)"),
+                )
+                .arg(
+                    Arg::with_name("ca-file")
+                        .long("ca-file")
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .help("Trust this PEM-encoded CA certificate instead of the platform store (wss:// only)"),
+                )
+                .arg(
+                    Arg::with_name("header")
+                        .short("H")
+                        .long("header")
+                        .help("Add a custom header, e.g. -H \"Origin: https://example.com\" (repeatable)")
+                        .value_name("NAME: VALUE")
+                        .takes_value(true)
+                        .number_of_values(1)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name("output-dir")
+                        .long("output-dir")
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .help("Save received binary frames as numbered files in this directory, instead of printing a hex dump"),
+                )
+                .arg(
+                    Arg::with_name("max-message-size")
+                        .long("max-message-size")
+                        .takes_value(true)
+                        .value_name("BYTES")
+                        .help("Maximum total message size to accept from the server"),
+                )
+                .arg(
+                    Arg::with_name("max-frame-size")
+                        .long("max-frame-size")
+                        .takes_value(true)
+                        .value_name("BYTES")
+                        .help("Maximum websocket frame size to accept from the server"),
+                )
+                .arg(
+                    Arg::with_name("accept-unmasked")
+                        .long("accept-unmasked")
+                        .help("Accept unmasked frames from the server (protocol violation, but some servers send them)"),
+                )
+                .arg(
+                    Arg::with_name("ping-interval")
+                        .long("ping-interval")
+                        .takes_value(true)
+                        .value_name("SECS")
+                        .help("Send a Ping every SECS seconds to detect a dead connection"),
+                )
+                .arg(
+                    Arg::with_name("max-missed-pings")
+                        .long("max-missed-pings")
+                        .takes_value(true)
+                        .value_name("N")
+                        .default_value("3")
+                        .help("Exit if this many consecutive pings go unanswered (only with --ping-interval)"),
+                )
+                .arg(
+                    Arg::with_name("exec")
+                        .long("exec")
+                        .takes_value(true)
+                        .value_name("COMMAND")
+                        .help("Spawn COMMAND in a pty and bridge it to the socket instead of the stdin/stdout prompt"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("listen")
+                .about("Start a WebSocket server and bridge it to stdin/stdout")
+                .arg(
+                    Arg::with_name("ADDR")
+                        .help("address:port to bind, e.g. 0.0.0.0:8080")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("echo")
+                        .long("echo")
+                        .help("Reflect each client's frames back to itself instead of printing them"),
+                )
+                .arg(
+                    Arg::with_name("exec")
+                        .long("exec")
+                        .takes_value(true)
+                        .value_name("COMMAND")
+                        .conflicts_with("echo")
+                        .help("Spawn COMMAND in a pty for each connecting client and bridge it to the socket"),
+                ),
+        )
+        .get_matches()
+}
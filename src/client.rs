@@ -1,17 +1,62 @@
 use ansi_term::Colour::{Green, Red, White};
-use anyhow::{Context as _, Result};
+use anyhow::{anyhow, Context as _, Result};
+use async_tungstenite::tungstenite::client::IntoClientRequest;
+use async_tungstenite::tungstenite::handshake::client::Request;
+use async_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use async_tungstenite::tungstenite::protocol::WebSocketConfig;
 use async_tungstenite::tungstenite::Message;
 use futures::{future, pin_mut};
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use futures::sink::SinkExt;
 use futures::stream::StreamExt;
+use futures_rustls::TlsConnector;
 use linefeed::{ReadResult, Signal};
+use rustls::ClientConfig;
+use serde::Deserialize;
 use smol::{Async, Task};
-use std::net::TcpStream;
-use std::process;
+use std::fs::File;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::os::unix::process::CommandExt;
+use std::pin::Pin;
+use std::process::{self, Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 use std::thread;
+use std::time::Duration;
 use url::Url;
 
+/// How to present a received `Message::Binary` frame, since it can't be assumed to be UTF-8.
+#[derive(Debug, Clone)]
+pub enum BinaryMode {
+    /// Print a `xxd`-style hex + ASCII dump to stdout.
+    Hex,
+    /// Write each frame to a numbered file under this directory and print its path.
+    File { dir: String },
+}
+
+/// Frame/message size limits and keepalive settings for the websocket connection.
+#[derive(Debug, Clone)]
+pub struct ProtocolOptions {
+    pub max_message_size: Option<usize>,
+    pub max_frame_size: Option<usize>,
+    pub accept_unmasked: bool,
+    pub ping_interval: Option<Duration>,
+    pub max_missed_pings: usize,
+}
+
+/// Options controlling how a `wss://` connection validates the server's certificate.
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    /// Skip certificate verification entirely (accepts self-signed servers).
+    pub no_check: bool,
+    /// Trust only the CA certificate(s) in this PEM file, instead of the platform store.
+    pub ca_file: Option<String>,
+}
+
 // Three threads:
 // - stdin loop
 // - stdout loop
@@ -20,9 +65,26 @@ use url::Url;
 // Use channels to communicate across threads.
 // - Crossbeam channel when receiver is in sync stdout
 // - piper when receiver is in websocket async
-//
-// First just support ws, not wss
-pub fn wscat_client(url: Url, _auth_option: Option<String>) -> Result<()> {
+pub fn wscat_client(
+    url: Url,
+    auth_option: Option<String>,
+    headers: Vec<(String, String)>,
+    tls_options: TlsOptions,
+    binary_mode: BinaryMode,
+    protocol_options: ProtocolOptions,
+    exec: Option<String>,
+) -> Result<()> {
+    let request = build_request(&url, auth_option, headers)?;
+
+    // --exec replaces the stdin/stdout prompt entirely with a pty bridge, so
+    // there's no readline loop to run on this thread.
+    if let Some(command) = exec {
+        return smol::run(async move {
+            let stream = connect_ws(&url, request, &tls_options, &protocol_options).await?;
+            bridge_pty(stream, command).await
+        });
+    }
+
     // set up channels for communicating
     let (tx_to_stdout, rx_stdout) = channel::<Message>(); // async -> sync
     let (tx_to_ws_write, rx_ws_write) = piper::chan::<Message>(10); // sync -> async, async -> async
@@ -34,7 +96,9 @@ pub fn wscat_client(url: Url, _auth_option: Option<String>) -> Result<()> {
     };
 
     // run read/write tasks for websocket
-    let ws_handle = thread::spawn(|| smol::run(ws_client(url, chans)));
+    let ws_handle = thread::spawn(|| {
+        smol::run(ws_client(url, request, chans, tls_options, binary_mode, protocol_options))
+    });
 
     // readline interface, which will hold read/write locks
     let readline = linefeed::Interface::new("manx")?;
@@ -59,8 +123,17 @@ pub fn wscat_client(url: Url, _auth_option: Option<String>) -> Result<()> {
         match readline.read_line()? {
             ReadResult::Input(input) => {
                 readline.add_history(input.clone());
-                // block on this
-                smol::block_on(tx_to_ws_write.send(Message::text(input)));
+                // "/send <path>" reads a file and sends it as a binary frame, so users can
+                // round-trip non-text payloads without typing them in as text.
+                if let Some(path) = input.strip_prefix("/send ") {
+                    match std::fs::read(path.trim()) {
+                        Ok(bytes) => smol::block_on(tx_to_ws_write.send(Message::Binary(bytes))),
+                        Err(err) => println!("{}", Red.paint(format!("can't read {:?}: {}", path.trim(), err))),
+                    }
+                } else {
+                    // block on this
+                    smol::block_on(tx_to_ws_write.send(Message::text(input)));
+                }
             },
             ReadResult::Signal(sig) => {
                 // If I don't exit process here, readline loop exits on first Interrupt, and then
@@ -77,21 +150,171 @@ pub fn wscat_client(url: Url, _auth_option: Option<String>) -> Result<()> {
     Ok(())
 }
 
+// Same shape as `wscat_client`, but bridges a listening socket to stdin/stdout
+// instead of a single outgoing connection: lines typed at the prompt are
+// broadcast to every connected client, and frames received from any client
+// are printed with the `<<` prefix. In `--echo` mode each client's frames are
+// reflected back to that same client instead, and in `--exec` mode each client
+// gets its own pty; clients in either mode receive no broadcast lines.
+pub fn wscat_server(addr: String, echo: bool, exec: Option<String>) -> Result<()> {
+    let (tx_to_stdout, rx_stdout) = channel::<Message>(); // async -> sync
+    let clients: Arc<Mutex<Vec<(u64, piper::Sender<Message>)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let server_clients = clients.clone();
+    let ws_handle = thread::spawn(|| smol::run(ws_server(addr, server_clients, tx_to_stdout, echo, exec)));
+
+    // readline interface, which will hold read/write locks
+    let readline = linefeed::Interface::new("manx")?;
+    readline.set_prompt("> ")?;
+    readline.set_report_signal(Signal::Interrupt, true);
+    let readline = Arc::new(readline);
+
+    //stdout loop
+    let stdout_readline = readline.clone();
+    let stdout_handle = thread::spawn(move || {
+        for message in rx_stdout {
+            if !(message.is_text() || message.is_binary()) {
+                continue;
+            }
+            let mut w = stdout_readline.lock_writer_erase().unwrap();
+            writeln!(w, "<< {}", message.into_text().unwrap()).unwrap();
+        }
+    });
+
+    // stdin loop
+    loop {
+        match readline.read_line()? {
+            ReadResult::Input(input) => {
+                readline.add_history(input.clone());
+                let message = Message::text(input);
+                // Snapshot the senders and release the lock before the (bounded,
+                // blocking) sends below: one backpressured or half-open client
+                // would otherwise hold `clients` locked indefinitely, freezing
+                // new connections (`push`) and disconnect cleanup (`retain`).
+                let senders: Vec<_> = clients.lock().unwrap().iter().map(|(_, s)| s.clone()).collect();
+                for client in senders {
+                    smol::block_on(client.send(message.clone()));
+                }
+            },
+            ReadResult::Signal(sig) => {
+                if sig == Signal::Interrupt { process::exit(0) };
+            },
+            _ => break,
+        }
+    }
+
+    ws_handle.join().unwrap().unwrap();
+    stdout_handle.join().unwrap();
+
+    Ok(())
+}
+
+// Accept loop: each incoming connection completes the websocket handshake and
+// is handed off to `handle_client`, which registers its broadcast-subscriber
+// channel in `clients` (non-echo, non-exec clients only).
+async fn ws_server(
+    addr: String,
+    clients: Arc<Mutex<Vec<(u64, piper::Sender<Message>)>>>,
+    tx_to_stdout: std::sync::mpsc::Sender<Message>,
+    echo: bool,
+    exec: Option<String>,
+) -> Result<()> {
+    let listener = Async::<TcpListener>::bind(&addr[..])
+        .with_context(|| format!("can't bind {:?}", addr))?;
+    let next_client_id = std::sync::atomic::AtomicU64::new(0);
+
+    loop {
+        let (stream, _peer_addr) = listener.accept().await?;
+        let stream = async_tungstenite::accept_async(stream).await?;
+        let id = next_client_id.fetch_add(1, Ordering::SeqCst);
+        let clients = clients.clone();
+        let tx_to_stdout = tx_to_stdout.clone();
+        let exec = exec.clone();
+
+        Task::local(handle_client(stream, id, clients, tx_to_stdout, echo, exec)).detach();
+    }
+}
+
+async fn handle_client(
+    stream: async_tungstenite::WebSocketStream<Async<TcpStream>>,
+    id: u64,
+    clients: Arc<Mutex<Vec<(u64, piper::Sender<Message>)>>>,
+    tx_to_stdout: std::sync::mpsc::Sender<Message>,
+    echo: bool,
+    exec: Option<String>,
+) -> Result<()> {
+    // --exec gives each connecting client its own pty-backed shell instead of
+    // joining the broadcast/echo loop below.
+    if let Some(command) = exec {
+        return bridge_pty(stream, command).await;
+    }
+
+    let (writer, mut reader) = stream.split();
+    let (tx_to_client, rx_to_client) = piper::chan::<Message>(10);
+    // Only broadcast-subscribe non-echo clients: an `--echo` client already gets
+    // its own frames reflected back below, and should stay isolated from lines
+    // typed at the server prompt and from other clients' traffic.
+    if !echo {
+        clients.lock().unwrap().push((id, tx_to_client.clone()));
+    }
+
+    let write_task = Task::local(async move {
+        rx_to_client.map(Ok).forward(writer).await
+    });
+
+    while let Some(message) = reader.next().await {
+        let message: Message = match message {
+            Ok(m) => m,
+            Err(_) => break,
+        };
+
+        if echo {
+            tx_to_client.send(message).await;
+        } else {
+            // Route through the same hex dump as the client's binary handling
+            // (chunk0-4) instead of forwarding the raw frame: `into_text()` in
+            // the stdout loop would panic on a non-UTF-8 binary frame.
+            let out = match message {
+                Message::Text(payload) => payload,
+                Message::Binary(payload) => format!("Binary frame ({} bytes):\n{}", payload.len(), hex_dump(&payload)),
+                _ => continue,
+            };
+            tx_to_stdout.send(Message::text(out)).unwrap();
+        }
+    }
+
+    // Evict this client's broadcast sender and drop our own clone so the
+    // channel actually closes once the client disconnects; otherwise a live
+    // sender stays parked in `clients` forever, lines typed at the prompt
+    // keep queuing into it, and `write_task` never sees the stream end.
+    clients.lock().unwrap().retain(|(client_id, _)| *client_id != id);
+    drop(tx_to_client);
+
+    write_task.await?;
+
+    Ok(())
+}
+
 // only use thread-local executor, since smol will only run on one thread
-async fn ws_client(url: Url, chans: WsChannels) -> Result<()> {
+async fn ws_client(
+    url: Url,
+    request: Request,
+    chans: WsChannels,
+    tls_options: TlsOptions,
+    binary_mode: BinaryMode,
+    protocol_options: ProtocolOptions,
+) -> Result<()> {
     let WsChannels {tx_to_ws_write, tx_to_stdout, rx_ws_write } = chans;
     let tx_to_ws_write = tx_to_ws_write.clone();
+    let frame_counter = AtomicUsize::new(0);
+    let missed_pings = Arc::new(AtomicUsize::new(0));
 
-    let host = url.host_str().context("can't parse host")?;
-    let port = url.port_or_known_default().context("can't guess port")?;
-    let addr = format!("{}:{}", host, port);
-
-    let stream = Async::<TcpStream>::connect(&addr).await?;
-    let (stream, _resp) = async_tungstenite::client_async(&url, stream).await?;
+    let stream = connect_ws(&url, request, &tls_options, &protocol_options).await?;
 
     let (writer, mut reader) = stream.split();
 
     // read task reads from ws, then sends signal to stdout loop
+    let read_missed_pings = missed_pings.clone();
     let read_task = Task::local(async move {
         while let Some(message) = reader.next().await {
             let message: Message = match message {
@@ -110,10 +333,19 @@ async fn ws_client(url: Url, chans: WsChannels) -> Result<()> {
                     tx_to_ws_write.send(Message::Pong(payload)).await;
                     format!("{}", Green.paint("Ping!\n")) //add color
                 },
+                Message::Pong(_) => {
+                    read_missed_pings.store(0, Ordering::SeqCst);
+                    continue;
+                },
                 Message::Text(payload) => { payload },
-                Message::Binary(payload) => {
-                    // Binary just supported as text here; no downloading, etc.
-                    String::from_utf8(payload).unwrap()
+                Message::Binary(payload) => match &binary_mode {
+                    BinaryMode::Hex => format!("Binary frame ({} bytes):\n{}", payload.len(), hex_dump(&payload)),
+                    BinaryMode::File { dir } => {
+                        match save_binary_frame(dir, &frame_counter, &payload) {
+                            Ok(path) => format!("Saved binary frame to {:?}", path),
+                            Err(err) => format!("{}", Red.paint(format!("couldn't save binary frame: {}", err))),
+                        }
+                    },
                 },
                 Message::Close(_) => {
                     println!("");
@@ -136,30 +368,383 @@ async fn ws_client(url: Url, chans: WsChannels) -> Result<()> {
     });
 
     pin_mut!(read_task, write_task);
-    future::select(read_task, write_task).await;
+    let io_task = future::select(read_task, write_task);
+
+    match protocol_options.ping_interval {
+        Some(interval) => {
+            let ping_task = Task::local(ping_loop(
+                tx_to_ws_write,
+                missed_pings,
+                interval,
+                protocol_options.max_missed_pings,
+            ));
+            pin_mut!(io_task, ping_task);
+            future::select(io_task, ping_task).await;
+        },
+        None => { io_task.await; },
+    }
+
+    Ok(())
+}
+
+// Sends a `Ping` every `interval`; `missed_pings` is incremented here and reset
+// to zero by the read task whenever a matching `Pong` comes back. Once
+// `max_missed_pings` consecutive pings go unanswered this exits the process
+// directly, the same way the read task's connection-closed path does (see
+// `process::exit(1)` above): this task runs on the background ws thread, so
+// merely returning `Err` here would unwind that thread while the main thread
+// stayed parked in `readline.read_line()`, leaving the dead connection's
+// prompt live instead of actually exiting.
+async fn ping_loop(
+    tx_to_ws_write: piper::Sender<Message>,
+    missed_pings: Arc<AtomicUsize>,
+    interval: Duration,
+    max_missed_pings: usize,
+) -> ! {
+    loop {
+        smol::Timer::after(interval).await;
+        // Checked before sending, so the previous ping has had a full interval
+        // to be answered before it counts as missed.
+        if missed_pings.load(Ordering::SeqCst) >= max_missed_pings {
+            println!("");
+            println!("{}", Red.paint(format!("no pong received after {} pings; connection appears dead", max_missed_pings)));
+            process::exit(1);
+        }
+        tx_to_ws_write.send(Message::Ping(Vec::new())).await;
+        missed_pings.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// Connects and performs the websocket handshake, branching on scheme for TLS.
+// Returns a single concrete stream type so callers (the normal read/write
+// loop, and the --exec pty bridge) don't need to care which branch was taken.
+async fn connect_ws(
+    url: &Url,
+    request: Request,
+    tls_options: &TlsOptions,
+    protocol_options: &ProtocolOptions,
+) -> Result<async_tungstenite::WebSocketStream<MaybeTlsStream<Async<TcpStream>>>> {
+    let host = url.host_str().context("can't parse host")?;
+    let port = url.port_or_known_default().context("can't guess port")?;
+    let addr = format!("{}:{}", host, port);
+
+    let tcp_stream = Async::<TcpStream>::connect(&addr).await?;
+
+    let stream = match url.scheme() {
+        "wss" => {
+            let connector = tls_connector(tls_options)?;
+            let domain = webpki::DNSNameRef::try_from_ascii_str(host)
+                .map_err(|_| anyhow!("invalid DNS name for TLS: {:?}", host))?;
+            MaybeTlsStream::Tls(connector.connect(domain, tcp_stream).await?)
+        },
+        _ => MaybeTlsStream::Plain(tcp_stream),
+    };
+
+    let ws_config = WebSocketConfig {
+        max_send_queue: None,
+        max_message_size: protocol_options.max_message_size,
+        max_frame_size: protocol_options.max_frame_size,
+        accept_unmasked_frames: protocol_options.accept_unmasked,
+    };
+
+    let (stream, _resp) = async_tungstenite::client_async_with_config(request, stream, Some(ws_config)).await?;
+    Ok(stream)
+}
+
+// Either a plain TCP stream or one wrapped in TLS, so `connect_ws` can return
+// one concrete type regardless of which branch it took.
+enum MaybeTlsStream<S> {
+    Plain(S),
+    Tls(futures_rustls::TlsStream<S>),
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for MaybeTlsStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite for MaybeTlsStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_flush(cx),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_close(cx),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_close(cx),
+        }
+    }
+}
+
+// Frames exchanged while bridging a pty are `Message::Binary` with a leading
+// control byte: `0` means the rest of the frame is raw pty bytes, `1` means
+// the rest is a `{"cols":_,"rows":_}` JSON resize request.
+const PTY_FRAME_DATA: u8 = 0;
+const PTY_FRAME_RESIZE: u8 = 1;
+
+#[derive(Deserialize)]
+struct PtyResize {
+    cols: u16,
+    rows: u16,
+}
+
+struct PtySession {
+    master: Async<File>,
+    // Reaped by `bridge_pty` once the bridge ends, via its pid.
+    child: Child,
+}
+
+impl PtySession {
+    fn spawn(command: &str) -> Result<Self> {
+        let pty = nix::pty::openpty(None, None).context("openpty failed")?;
+        let slave_fd = pty.slave;
+        let master_fd = pty.master;
+
+        // Each `Stdio` closes its fd on drop, so stdin/stdout/stderr each need their
+        // own fd: dup the slave twice and let the third `Stdio` own `slave_fd`
+        // itself, instead of wrapping the same fd in three owners (which would
+        // close it up to three times, racing whatever fd got reused in between).
+        let stdin_fd = nix::unistd::dup(slave_fd).context("can't dup pty slave fd")?;
+        let stdout_fd = nix::unistd::dup(slave_fd).context("can't dup pty slave fd")?;
+
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd.stdin(unsafe { Stdio::from_raw_fd(stdin_fd) });
+        cmd.stdout(unsafe { Stdio::from_raw_fd(stdout_fd) });
+        cmd.stderr(unsafe { Stdio::from_raw_fd(slave_fd) });
+
+        unsafe {
+            cmd.pre_exec(move || {
+                nix::unistd::setsid().map_err(|err| std::io::Error::from_raw_os_error(err as i32))?;
+                if libc::ioctl(0, libc::TIOCSCTTY as _, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        let child = cmd.spawn().context("can't spawn --exec command")?;
+
+        let master = Async::new(unsafe { File::from_raw_fd(master_fd) })?;
+
+        Ok(PtySession { master, child })
+    }
+
+    fn resize(&self, cols: u16, rows: u16) -> Result<()> {
+        let winsize = nix::pty::Winsize { ws_row: rows, ws_col: cols, ws_xpixel: 0, ws_ypixel: 0 };
+        let res = unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &winsize) };
+        if res != 0 {
+            return Err(anyhow!(std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
+
+// Bridges an already-established websocket to a freshly spawned pty: pty
+// output is framed as data and sent out, incoming data frames are written to
+// the pty, and incoming resize frames drive a `TIOCSWINSZ` ioctl.
+async fn bridge_pty<S>(stream: async_tungstenite::WebSocketStream<S>, command: String) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (mut writer, mut reader) = stream.split();
+    let pty = Arc::new(PtySession::spawn(&command)?);
+
+    let to_ws_pty = pty.clone();
+    let to_ws = async move {
+        let mut buf = [0u8; 4096];
+        loop {
+            let mut master = &to_ws_pty.master;
+            let n = match master.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            let mut frame = Vec::with_capacity(n + 1);
+            frame.push(PTY_FRAME_DATA);
+            frame.extend_from_slice(&buf[..n]);
+            if writer.send(Message::Binary(frame)).await.is_err() {
+                break;
+            }
+        }
+    };
+
+    let from_ws_pty = pty.clone();
+    let from_ws = async move {
+        while let Some(message) = reader.next().await {
+            let message = match message {
+                Ok(m) => m,
+                Err(_) => break,
+            };
+            let frame = match message {
+                Message::Binary(frame) => frame,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            match frame.split_first() {
+                Some((&PTY_FRAME_DATA, rest)) => {
+                    let mut master = &from_ws_pty.master;
+                    if master.write_all(rest).await.is_err() {
+                        break;
+                    }
+                },
+                Some((&PTY_FRAME_RESIZE, rest)) => {
+                    if let Ok(resize) = serde_json::from_slice::<PtyResize>(rest) {
+                        from_ws_pty.resize(resize.cols, resize.rows).ok();
+                    }
+                },
+                _ => {},
+            }
+        }
+    };
+
+    future::select(Box::pin(to_ws), Box::pin(from_ws)).await;
+
+    // `select` drops whichever of `to_ws`/`from_ws` didn't finish, so `pty`
+    // below is the last `Arc` reference; dropping it closes the pty master,
+    // which raises SIGHUP on the child's session. Poll non-blockingly for
+    // it to exit so --exec doesn't leak a zombie per connection instead of
+    // waiting forever on a child that ignores the signal.
+    let child_pid = nix::unistd::Pid::from_raw(pty.child.id() as i32);
+    drop(pty);
+    for _ in 0..20 {
+        match nix::sys::wait::waitpid(child_pid, Some(nix::sys::wait::WaitPidFlag::WNOHANG)) {
+            Ok(nix::sys::wait::WaitStatus::StillAlive) => {
+                smol::Timer::after(Duration::from_millis(50)).await;
+            },
+            _ => break,
+        }
+    }
 
     Ok(())
 }
 
+// Builds the TLS config for a `wss://` connection: trust roots come from a pinned
+// `--ca-file`, the platform store, or a bundled webpki-roots set, in that order.
+// `--no-check` disables verification entirely, for self-signed test servers.
+fn tls_connector(tls_options: &TlsOptions) -> Result<TlsConnector> {
+    let mut config = ClientConfig::new();
+
+    if tls_options.no_check {
+        config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(danger::NoCertificateVerification));
+    } else if let Some(ca_file) = &tls_options.ca_file {
+        let f = File::open(ca_file)
+            .with_context(|| format!("can't open CA file {:?}", ca_file))?;
+        let mut reader = BufReader::new(f);
+        config
+            .root_store
+            .add_pem_file(&mut reader)
+            .map_err(|_| anyhow!("can't parse CA file {:?} as PEM", ca_file))?;
+    } else {
+        let native_roots = rustls_native_certs::load_native_certs();
+        match native_roots {
+            Ok(store) => config.root_store = store,
+            Err(_) => {
+                config
+                    .root_store
+                    .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+            },
+        }
+    }
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+// Only reached with `--no-check`; deliberately accepts any certificate.
+mod danger {
+    use rustls::{Certificate, RootCertStore, ServerCertVerified, ServerCertVerifier, TLSError};
+    use webpki::DNSNameRef;
+
+    pub struct NoCertificateVerification;
+
+    impl ServerCertVerifier for NoCertificateVerification {
+        fn verify_server_cert(
+            &self,
+            _roots: &RootCertStore,
+            _presented_certs: &[Certificate],
+            _dns_name: DNSNameRef<'_>,
+            _ocsp_response: &[u8],
+        ) -> Result<ServerCertVerified, TLSError> {
+            Ok(ServerCertVerified::assertion())
+        }
+    }
+}
+
+// `xxd`-style hex + ASCII dump, 16 bytes per line.
+fn hex_dump(payload: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, line) in payload.chunks(16).enumerate() {
+        let hex: Vec<String> = line.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = line.iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}  {:<47}  {}\n", i * 16, hex.join(" "), ascii));
+    }
+    out
+}
+
+// Writes a binary frame to `<dir>/frame-NNNN.bin`, numbered by `counter`.
+fn save_binary_frame(dir: &str, counter: &AtomicUsize, payload: &[u8]) -> Result<String> {
+    std::fs::create_dir_all(dir).with_context(|| format!("can't create {:?}", dir))?;
+    let n = counter.fetch_add(1, Ordering::SeqCst);
+    let path = format!("{}/frame-{:04}.bin", dir, n);
+    std::fs::write(&path, payload).with_context(|| format!("can't write {:?}", path))?;
+    Ok(path)
+}
+
 struct WsChannels {
     tx_to_ws_write: piper::Sender<Message>,
     tx_to_stdout: std::sync::mpsc::Sender<Message>,
     rx_ws_write: piper::Receiver<Message>,
 }
 
+// Builds the handshake request: the target url, plus an `Authorization: Basic`
+// header if credentials were given, plus any user-supplied `-H` headers.
+fn build_request(
+    url: &Url,
+    auth_option: Option<String>,
+    headers: Vec<(String, String)>,
+) -> Result<Request> {
+    let mut request = url.as_str().into_client_request()?;
+    let request_headers = request.headers_mut();
+
+    if let Some(auth) = auth_option {
+        request_headers.insert(
+            "Authorization",
+            HeaderValue::from_str(&auth).context("invalid Authorization header value")?,
+        );
+    }
 
-// TODO do this later
-// refactor to use from_str
-//pub fn parse_authorization(user_password: &str) -> Option<Authorization<Basic>> {
-//    let v: Vec<_> = user_password.split(':').collect();
-//    if v.len() > 2 {
-//        None
-//    } else {
-//        Some(Authorization (
-//            Basic {
-//                username: v[0].to_owned(),
-//                password: v.get(1).map(|&p| p.to_owned()),
-//            }
-//        ))
-//    }
-//}
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("invalid header name {:?}", name))?;
+        let header_value = HeaderValue::from_str(&value)
+            .with_context(|| format!("invalid value for header {:?}: {:?}", name, value))?;
+        request_headers.append(header_name, header_value);
+    }
+
+    Ok(request)
+}
+
+// Splits "USERNAME:PASSWORD" (password optional) into a `Basic` Authorization header value.
+pub fn parse_authorization(user_password: &str) -> String {
+    let mut parts = user_password.splitn(2, ':');
+    let username = parts.next().unwrap_or("");
+    let password = parts.next().unwrap_or("");
+    format!("Basic {}", base64::encode(format!("{}:{}", username, password)))
+}
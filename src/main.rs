@@ -17,17 +17,68 @@ fn main() -> Result<()> {
             let url: Url = url_option.parse()
                 .with_context(|| format!("Error parsing {:?}", url_option))?;
 
-            // TODO later
-            //let auth_option = matches.value_of("USERNAME:PASSWORD")
-            //    .and_then(|user_pass| {
-            //        parse_authorization(user_pass)
-            //    });
-            let auth_option = None;
+            let auth_option = matches.value_of("USERNAME:PASSWORD")
+                .map(|user_pass| client::parse_authorization(user_pass));
+
+            let headers = match matches.values_of("header") {
+                Some(values) => values
+                    .map(|header| {
+                        let mut parts = header.splitn(2, ':');
+                        let name = parts.next().unwrap_or("").trim().to_owned();
+                        let value = parts.next().unwrap_or("").trim().to_owned();
+                        (name, value)
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+
+            let tls_options = client::TlsOptions {
+                no_check: matches.is_present("no-check"),
+                ca_file: matches.value_of("ca-file").map(String::from),
+            };
+
+            let binary_mode = match matches.value_of("output-dir") {
+                Some(dir) => client::BinaryMode::File { dir: dir.to_owned() },
+                None => client::BinaryMode::Hex,
+            };
+
+            let protocol_options = client::ProtocolOptions {
+                max_message_size: matches.value_of("max-message-size")
+                    .map(|s| s.parse())
+                    .transpose()
+                    .context("--max-message-size must be a number of bytes")?,
+                max_frame_size: matches.value_of("max-frame-size")
+                    .map(|s| s.parse())
+                    .transpose()
+                    .context("--max-frame-size must be a number of bytes")?,
+                accept_unmasked: matches.is_present("accept-unmasked"),
+                ping_interval: matches.value_of("ping-interval")
+                    .map(|s| s.parse().map(std::time::Duration::from_secs))
+                    .transpose()
+                    .context("--ping-interval must be a number of seconds")?,
+                max_missed_pings: matches.value_of("max-missed-pings")
+                    .unwrap()
+                    .parse()
+                    .context("--max-missed-pings must be a number")?,
+            };
+
+            let exec = matches.value_of("exec").map(String::from);
 
             // print that client is connecting
             let out_url = format!("Connected to {:?} (Ctrl-C to exit)", url_option);
             println!("{}", Blue.bold().paint(out_url));
-            client::wscat_client(url, auth_option)?;
+            client::wscat_client(url, auth_option, headers, tls_options, binary_mode, protocol_options, exec)?;
+        }
+    }
+
+    if let Some(ref matches) = matches.subcommand_matches("listen") {
+        if let Some(addr) = matches.value_of("ADDR") {
+            let echo = matches.is_present("echo");
+            let exec = matches.value_of("exec").map(String::from);
+
+            let out = format!("Listening on {:?} (Ctrl-C to exit)", addr);
+            println!("{}", Blue.bold().paint(out));
+            client::wscat_server(addr.to_owned(), echo, exec)?;
         }
     }
 